@@ -0,0 +1,959 @@
+use cgmath::{InnerSpace, Rad, Rotation3, Zero};
+use serde::{
+    ser::{SerializeMap, SerializeSeq},
+    Serialize, Serializer,
+};
+
+use crate::dyn_iter::DynIterMut;
+use crate::session::SessionId;
+use crate::universe::Universe;
+use crate::{Quaternion, Vector3, AU};
+
+const EPSILON: f64 = 1e-40; // Doesn't the machine epsilon depend on browsers!??
+const PARABOLIC_TOLERANCE: f64 = 1e-6;
+
+/// Which conic a body's state vectors describe, so the front end can pick
+/// the matching curve primitive instead of assuming a closed ellipse.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Default)]
+pub enum OrbitType {
+    #[default]
+    Elliptic,
+    Parabolic,
+    Hyperbolic,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct OrbitalElements {
+    pub semimajor_axis: f64,
+    pub ascending_node: f64,
+    pub inclination: f64,
+    pub eccentricity: f64,
+    pub epoch: f64,
+    pub mean_anomaly: f64,
+    pub argument_of_perihelion: f64,
+    pub soi: f64,
+    pub orbit_type: OrbitType,
+}
+
+/// How a body's position/velocity are advanced each frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PropagationKind {
+    /// Advance the stored mean anomaly by the two-body mean motion and solve
+    /// Kepler's equation for the resulting state. Exact for unperturbed orbits.
+    Analytic,
+    /// Step position/velocity forward from accumulated gravitational acceleration.
+    /// Needed while a body is thrusting or otherwise not on a pure conic.
+    Numeric,
+}
+
+#[derive(Clone, Copy)]
+pub struct AddPlanetParams {
+    pub axial_tilt: f64,
+    pub rotation_period: f64,
+    pub quaternion: Quaternion,
+    pub angular_velocity: Vector3,
+}
+
+impl Default for AddPlanetParams {
+    fn default() -> Self {
+        Self {
+            axial_tilt: 0.,
+            rotation_period: 0.,
+            quaternion: Quaternion::new(1., 0., 0., 0.),
+            angular_velocity: Vector3::zero(),
+        }
+    }
+}
+
+/// Placeholder physical properties for a `CelestialBody::from_tle` import,
+/// grouped since a TLE alone carries no mass or size information (same
+/// placeholder pattern as `Universe::new_rocket`).
+#[allow(non_snake_case)]
+#[derive(Clone, Copy)]
+pub struct TleBodyParams {
+    pub GM: f64,
+    pub radius: f64,
+    pub params: AddPlanetParams,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug)]
+pub struct CelestialBody {
+    pub id: usize,
+    pub name: String,
+    pub session_id: Option<SessionId>,
+
+    pub(crate) position: Vector3,
+    pub(crate) velocity: Vector3,
+    pub(crate) quaternion: Quaternion,
+    pub(crate) angular_velocity: Vector3,
+    orbit_color: String,
+    // orbitMaterial: THREE.LineBasicMaterial;
+    pub(crate) children: Vec<usize>,
+    pub(crate) parent: Option<usize>,
+
+    pub(crate) GM: f64,
+    radius: f64,
+
+    /// Zonal-harmonic oblateness coefficient (e.g. Earth's J2 ~= 1.08263e-3).
+    /// Zero for bodies that can be treated as point masses.
+    pub(crate) j2: f64,
+    /// Equatorial radius used to scale the J2 perturbation felt by children
+    /// orbiting this body. Unused unless `j2` is nonzero.
+    pub(crate) equatorial_radius: f64,
+
+    pub(crate) propagation: PropagationKind,
+    orbital_elements: OrbitalElements,
+}
+
+/// Solve Kepler's equation `M = E - e*sin(E)` for the eccentric anomaly via
+/// Newton-Raphson, starting from `E0 = M`.
+fn solve_eccentric_anomaly(mean_anomaly: f64, eccentricity: f64) -> f64 {
+    let mut e_anom = mean_anomaly;
+    for _ in 0..30 {
+        let delta = (e_anom - eccentricity * e_anom.sin() - mean_anomaly)
+            / (1. - eccentricity * e_anom.cos());
+        e_anom -= delta;
+        if delta.abs() < 1e-12 {
+            break;
+        }
+    }
+    e_anom
+}
+
+/// Solve the hyperbolic Kepler equation `M = e*sinh(H) - H` for the
+/// hyperbolic anomaly via Newton-Raphson, starting from `H0 = M`.
+fn solve_hyperbolic_anomaly(mean_anomaly: f64, eccentricity: f64) -> f64 {
+    let mut h_anom = mean_anomaly;
+    for _ in 0..30 {
+        let delta = (eccentricity * h_anom.sinh() - h_anom - mean_anomaly)
+            / (eccentricity * h_anom.cosh() - 1.);
+        h_anom -= delta;
+        if delta.abs() < 1e-12 {
+            break;
+        }
+    }
+    h_anom
+}
+
+/// Solve Barker's equation `M = D + D^3/3` (with `D = tan(nu/2)`) for the
+/// true anomaly of a near-parabolic orbit, where the eccentric/hyperbolic
+/// anomaly solvers above are ill-conditioned as `e` approaches 1.
+fn solve_parabolic_true_anomaly(mean_anomaly: f64) -> f64 {
+    let mut d = mean_anomaly;
+    for _ in 0..30 {
+        let delta = (d + d.powi(3) / 3. - mean_anomaly) / (1. + d * d);
+        d -= delta;
+        if delta.abs() < 1e-12 {
+            break;
+        }
+    }
+    2. * d.atan()
+}
+
+/// Classify the conic described by `eccentricity`, matching the tolerance
+/// used to fall back to Barker's equation for near-parabolic orbits.
+fn classify_orbit(eccentricity: f64) -> OrbitType {
+    if (eccentricity - 1.).abs() < PARABOLIC_TOLERANCE {
+        OrbitType::Parabolic
+    } else if eccentricity > 1. {
+        OrbitType::Hyperbolic
+    } else {
+        OrbitType::Elliptic
+    }
+}
+
+/// Rotation carrying the perifocal frame (periapsis along y, direction of
+/// motion at periapsis along x) into the parent's frame. Mirrors the ascending-node/inclination
+/// rotation chain built in `CelestialBody::update`, plus the argument of
+/// perihelion that `update` instead recovers from the state vectors.
+fn perifocal_to_parent_rotation(elements: &OrbitalElements) -> Quaternion {
+    let ascending_node_rot = <Quaternion as Rotation3>::from_axis_angle(
+        Vector3::new(0., 0., 1.),
+        Rad(elements.ascending_node - std::f64::consts::PI / 2.),
+    );
+    let inclination_rot = Quaternion::from_axis_angle(
+        Vector3::new(0., 1., 0.),
+        Rad(std::f64::consts::PI - elements.inclination),
+    );
+    let argument_of_perihelion_rot = Quaternion::from_axis_angle(
+        Vector3::new(0., 0., 1.),
+        Rad(elements.argument_of_perihelion),
+    );
+    ascending_node_rot * inclination_rot * argument_of_perihelion_rot
+}
+
+/// True anomaly of a body on the orbit described by `elements`, dispatching
+/// to the conic-appropriate anomaly solver.
+fn true_anomaly_from_elements(elements: &OrbitalElements) -> f64 {
+    let e = elements.eccentricity;
+    match elements.orbit_type {
+        OrbitType::Elliptic => {
+            let eccentric_anomaly = solve_eccentric_anomaly(elements.mean_anomaly, e);
+            2. * ((1. + e).sqrt() * (eccentric_anomaly / 2.).sin())
+                .atan2((1. - e).sqrt() * (eccentric_anomaly / 2.).cos())
+        }
+        OrbitType::Hyperbolic => {
+            let hyperbolic_anomaly = solve_hyperbolic_anomaly(elements.mean_anomaly, e);
+            2. * ((e + 1.).sqrt() * (hyperbolic_anomaly / 2.).tanh())
+                .atan2((e - 1.).sqrt())
+        }
+        OrbitType::Parabolic => solve_parabolic_true_anomaly(elements.mean_anomaly),
+    }
+}
+
+/// Position and velocity, in the parent's frame, of a body on the orbit
+/// described by `elements` around a focus of gravitational parameter
+/// `parent_gm`. Works uniformly across ellipses, parabolas and hyperbolas:
+/// both are expressed in terms of the true anomaly and the semi-latus
+/// rectum `p`, which stays well-defined even as `e` approaches 1.
+fn state_from_elements(parent_gm: f64, elements: &OrbitalElements) -> (Vector3, Vector3) {
+    let e = elements.eccentricity;
+    let true_anomaly = true_anomaly_from_elements(elements);
+
+    let p = elements.semimajor_axis * (1. - e * e);
+    let r = p / (1. + e * true_anomaly.cos());
+    let h = (parent_gm * p).sqrt();
+
+    // Perifocal position and velocity (periapsis along y, direction of motion
+    // at periapsis along x), the frame whose elements `update` recovers.
+    let position = Vector3::new(r * true_anomaly.sin(), r * true_anomaly.cos(), 0.);
+    let velocity = Vector3::new(
+        (parent_gm / h) * (e + true_anomaly.cos()),
+        -(parent_gm / h) * true_anomaly.sin(),
+        0.,
+    );
+
+    let rot = perifocal_to_parent_rotation(elements);
+    (rot * position, rot * velocity)
+}
+
+/// Mean anomaly consistent with `true_anomaly` on an orbit of the given
+/// `eccentricity`/`orbit_type`. The inverse of `true_anomaly_from_elements`;
+/// needed whenever a body's state is set from outside (e.g. a sphere-of-
+/// influence transition), since the analytic propagator only ever advances
+/// the mean anomaly it is already holding.
+fn mean_anomaly_from_true_anomaly(true_anomaly: f64, eccentricity: f64, orbit_type: OrbitType) -> f64 {
+    let e = eccentricity;
+    match orbit_type {
+        OrbitType::Elliptic => {
+            let eccentric_anomaly = 2.
+                * ((1. - e).sqrt() * (true_anomaly / 2.).sin())
+                    .atan2((1. + e).sqrt() * (true_anomaly / 2.).cos());
+            eccentric_anomaly - e * eccentric_anomaly.sin()
+        }
+        OrbitType::Hyperbolic => {
+            let hyperbolic_anomaly =
+                2. * (((e - 1.) / (e + 1.)).sqrt() * (true_anomaly / 2.).tan()).atanh();
+            e * hyperbolic_anomaly.sinh() - hyperbolic_anomaly
+        }
+        OrbitType::Parabolic => {
+            let d = (true_anomaly / 2.).tan();
+            d + d.powi(3) / 3.
+        }
+    }
+}
+
+/// Point-mass + (if nonzero) J2 gravitational acceleration felt at
+/// `position` due to a parent of gravitational parameter `parent_gm`,
+/// oblateness `parent_j2`/`parent_equatorial_radius` and orientation
+/// `parent_quaternion`. Shared by the per-body stepping in `simulate_body`
+/// and the whole-field leapfrog integrator in `Universe::update`, so both
+/// integrators feel the same force model.
+pub(crate) fn gravitational_acceleration(
+    position: Vector3,
+    parent_gm: f64,
+    parent_j2: f64,
+    parent_equatorial_radius: f64,
+    parent_quaternion: Quaternion,
+) -> Vector3 {
+    let r = position.magnitude();
+    let mut acceleration = -position * (parent_gm / r.powi(3));
+    if parent_j2 != 0. {
+        acceleration += j2_acceleration(
+            position,
+            parent_gm,
+            parent_j2,
+            parent_equatorial_radius,
+            parent_quaternion,
+        );
+    }
+    acceleration
+}
+
+/// J2 zonal-harmonic perturbing acceleration felt at `position` (in the
+/// parent's frame) due to the parent's equatorial bulge, rotated from the
+/// parent's equatorial frame (pole along its body-fixed z) back into the
+/// frame `position` is expressed in via the parent's `quaternion`.
+fn j2_acceleration(
+    position: Vector3,
+    parent_gm: f64,
+    parent_j2: f64,
+    parent_equatorial_radius: f64,
+    parent_quaternion: Quaternion,
+) -> Vector3 {
+    use cgmath::Rotation;
+
+    let r_eq = parent_quaternion.invert() * position;
+    let r = r_eq.magnitude();
+    let z_over_r = r_eq.z / r;
+    let scale = -1.5
+        * parent_j2
+        * (parent_gm / (r * r))
+        * (parent_equatorial_radius / r) * (parent_equatorial_radius / r);
+
+    let a_eq = Vector3::new(
+        scale * (1. - 5. * z_over_r * z_over_r) * r_eq.x / r,
+        scale * (1. - 5. * z_over_r * z_over_r) * r_eq.y / r,
+        scale * (3. - 5. * z_over_r * z_over_r) * r_eq.z / r,
+    );
+
+    parent_quaternion * a_eq
+}
+
+/// Revolutions/day, as published in TLE mean motion, converted to radians/s
+/// to match the crate's internal (seconds-based) angular rates.
+const TLE_REV_PER_DAY_TO_RAD_PER_SEC: f64 = 2. * std::f64::consts::PI / 86400.;
+
+/// Why a TLE line could not be parsed. Carries just enough to locate the
+/// problem in a pasted catalog export without needing a dependency on a
+/// full SGP4/TLE crate's error type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TleParseError {
+    /// A line was shorter than the fixed-width column it was sliced on.
+    LineTooShort { line: usize, needed: usize, got: usize },
+    /// A fixed-width field wasn't the number it claimed to be.
+    InvalidField { line: usize, field: &'static str },
+}
+
+impl std::fmt::Display for TleParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TleParseError::LineTooShort { line, needed, got } => write!(
+                f,
+                "TLE line {line} is too short: needed at least {needed} columns, got {got}"
+            ),
+            TleParseError::InvalidField { line, field } => {
+                write!(f, "TLE line {line} has an invalid {field} field")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TleParseError {}
+
+/// Slice `line` to the fixed-width `range`, checking it's wide enough first
+/// since a truncated pasted line would otherwise panic on out-of-bounds indexing.
+fn tle_column<'a>(
+    line: &'a str,
+    line_number: usize,
+    range: std::ops::Range<usize>,
+) -> Result<&'a str, TleParseError> {
+    line.get(range.clone())
+        .ok_or(TleParseError::LineTooShort {
+            line: line_number,
+            needed: range.end,
+            got: line.len(),
+        })
+}
+
+/// Parse one fixed-width TLE field as `T`, reporting which named field
+/// failed rather than panicking on malformed input.
+fn tle_field<T: std::str::FromStr>(
+    line: &str,
+    line_number: usize,
+    range: std::ops::Range<usize>,
+    field: &'static str,
+) -> Result<T, TleParseError> {
+    tle_column(line, line_number, range)?
+        .trim()
+        .parse()
+        .map_err(|_| TleParseError::InvalidField {
+            line: line_number,
+            field,
+        })
+}
+
+/// Parse the classical orbital elements and epoch out of a standard
+/// two-line element set (TLE), recovering `semimajor_axis` from the mean
+/// motion via Kepler's third law around a focus of gravitational parameter
+/// `parent_gm` (in this crate's normalized units). Column layout follows the
+/// sgp4-rs crate's `Elements::from_tle`, but since TLE-seeded bodies are
+/// propagated on analytic two-body Kepler orbits, none of the SGP4
+/// perturbation terms (drag, J2/J4 secular rates) are consumed.
+fn orbital_elements_from_tle(
+    parent_gm: f64,
+    line1: &str,
+    line2: &str,
+) -> Result<OrbitalElements, TleParseError> {
+    let rad_per_deg = std::f64::consts::PI / 180.;
+
+    let epoch_year: i32 = tle_field(line1, 1, 18..20, "epoch year")?;
+    let epoch_year = if epoch_year < 57 {
+        2000 + epoch_year
+    } else {
+        1900 + epoch_year
+    };
+    let epoch_day: f64 = tle_field(line1, 1, 20..32, "epoch day")?;
+    // Days since the 1950 epoch conventionally used for TLE bookkeeping.
+    let epoch = (epoch_year - 1950) as f64 * 365.25 + epoch_day;
+
+    let inclination: f64 = tle_field(line2, 2, 8..16, "inclination")?;
+    let ascending_node: f64 = tle_field(line2, 2, 17..25, "ascending node")?;
+    let eccentricity: f64 = format!("0.{}", tle_column(line2, 2, 26..33)?.trim())
+        .parse()
+        .map_err(|_| TleParseError::InvalidField {
+            line: 2,
+            field: "eccentricity",
+        })?;
+    let argument_of_perihelion: f64 = tle_field(line2, 2, 34..42, "argument of perihelion")?;
+    let mean_anomaly: f64 = tle_field(line2, 2, 43..51, "mean anomaly")?;
+    let mean_motion: f64 = tle_field(line2, 2, 52..63, "mean motion")?;
+
+    let mean_motion_rad_per_sec = mean_motion * TLE_REV_PER_DAY_TO_RAD_PER_SEC;
+    let semimajor_axis = (parent_gm / (mean_motion_rad_per_sec * mean_motion_rad_per_sec)).cbrt();
+
+    Ok(OrbitalElements {
+        semimajor_axis,
+        ascending_node: ascending_node * rad_per_deg,
+        inclination: inclination * rad_per_deg,
+        eccentricity,
+        epoch,
+        mean_anomaly: mean_anomaly * rad_per_deg,
+        argument_of_perihelion: argument_of_perihelion * rad_per_deg,
+        soi: 0.,
+        orbit_type: classify_orbit(eccentricity),
+    })
+}
+
+impl CelestialBody {
+    /// Create the root body of a `Universe` (a body with no parent, e.g. the sun).
+    #[allow(non_snake_case)]
+    pub fn new(
+        universe: &mut Universe,
+        parent: Option<usize>,
+        position: Vector3,
+        orbit_color: String,
+        GM: f64,
+        name: String,
+        orbital_elements: OrbitalElements,
+    ) -> Self {
+        let id = universe.id_gen;
+        universe.id_gen += 1;
+        Self {
+            id,
+            name,
+            session_id: None,
+            position,
+            velocity: Vector3::zero(),
+            quaternion: Quaternion::new(1., 0., 0., 0.),
+            angular_velocity: Vector3::zero(),
+            orbit_color,
+            children: vec![],
+            parent,
+            GM,
+            radius: 0.,
+            j2: 0.,
+            equatorial_radius: 0.,
+            propagation: PropagationKind::Analytic,
+            orbital_elements,
+        }
+    }
+
+    /// Create a body orbiting `parent`, placed at the state implied by
+    /// `orbital_elements` at its epoch.
+    #[allow(non_snake_case)]
+    pub fn from_orbital_elements(
+        universe: &mut Universe,
+        parent: Option<usize>,
+        orbital_elements: OrbitalElements,
+        params: AddPlanetParams,
+        GM: f64,
+        radius: f64,
+        name: String,
+    ) -> Self {
+        let parent_gm = parent
+            .and_then(|id| universe.bodies.iter().find(|body| body.id == id))
+            .map(|body| body.GM)
+            .unwrap_or(GM);
+        let (position, velocity) = state_from_elements(parent_gm, &orbital_elements);
+
+        // Axial tilt rotates the body's equatorial plane away from the
+        // ecliptic around the line of equinoxes (the x-axis); fold it into
+        // whatever base orientation the caller supplied so J2 and other
+        // frame-relative effects see the body's true spin axis.
+        let tilt = <Quaternion as Rotation3>::from_angle_x(Rad(params.axial_tilt));
+
+        let id = universe.id_gen;
+        universe.id_gen += 1;
+        Self {
+            id,
+            name,
+            session_id: None,
+            position,
+            velocity,
+            quaternion: tilt * params.quaternion,
+            angular_velocity: params.angular_velocity,
+            orbit_color: "#ffffff".to_string(),
+            children: vec![],
+            parent,
+            GM,
+            radius,
+            j2: 0.,
+            equatorial_radius: 0.,
+            propagation: PropagationKind::Analytic,
+            orbital_elements,
+        }
+    }
+
+    /// Create a body on the orbit described by a standard two-line element
+    /// set (TLE), e.g. as published by Celestrak, around `parent` (in
+    /// practice: Earth). Reuses `from_orbital_elements`, so the satellite
+    /// then coasts on the osculating orbit recovered from the TLE via the
+    /// analytic Kepler path rather than reproducing SGP4's perturbations.
+    ///
+    /// Returns `Err` if either line isn't a well-formed TLE line (wrong
+    /// width or a non-numeric field), since this is the crate's only parser
+    /// for externally-sourced, hand-pasted catalog text.
+    pub fn from_tle(
+        universe: &mut Universe,
+        parent: Option<usize>,
+        line1: &str,
+        line2: &str,
+        name: String,
+        physical: TleBodyParams,
+    ) -> Result<Self, TleParseError> {
+        let parent_gm = parent
+            .and_then(|id| universe.bodies.iter().find(|body| body.id == id))
+            .map(|body| body.GM)
+            .unwrap_or(physical.GM);
+        let orbital_elements = orbital_elements_from_tle(parent_gm, line1, line2)?;
+        Ok(Self::from_orbital_elements(
+            universe,
+            parent,
+            orbital_elements,
+            physical.params,
+            physical.GM,
+            physical.radius,
+            name,
+        ))
+    }
+
+    /// Set the zonal-harmonic oblateness felt by this body's children, e.g.
+    /// `earth.with_j2(1.08263e-3, 6378.137 / AU)`.
+    pub fn with_j2(mut self, j2: f64, equatorial_radius: f64) -> Self {
+        self.j2 = j2;
+        self.equatorial_radius = equatorial_radius;
+        self
+    }
+
+    /// Radius of this body's sphere of influence, within which its children
+    /// are expected to orbit it rather than its own parent.
+    pub fn soi(&self) -> f64 {
+        self.orbital_elements.soi
+    }
+
+    /// Integrate this body forward by one substep of `dt`, using the
+    /// gravitational influence of `others` (in practice: its parent).
+    pub(crate) fn simulate_body(
+        &mut self,
+        mut others: impl DynIterMut<Item = CelestialBody>,
+        dt: f64,
+    ) {
+        let parent = match self.parent {
+            Some(parent_id) => others.dyn_iter_mut().find(|body| body.id == parent_id),
+            None => None,
+        };
+        let Some(parent) = parent else {
+            return;
+        };
+        let parent_gm = parent.GM;
+
+        match self.propagation {
+            PropagationKind::Analytic => {
+                let elements = &self.orbital_elements;
+                let mean_motion = if elements.orbit_type == OrbitType::Parabolic {
+                    // Barker's `D + D^3/3` advances at 2*sqrt(GM/p^3).
+                    let p = elements.semimajor_axis
+                        * (1. - elements.eccentricity * elements.eccentricity);
+                    2. * (parent_gm / p.abs().powi(3)).sqrt()
+                } else {
+                    (parent_gm / elements.semimajor_axis.abs().powi(3)).sqrt()
+                };
+                self.orbital_elements.mean_anomaly += mean_motion * dt;
+                let (position, velocity) = state_from_elements(parent_gm, &self.orbital_elements);
+                self.position = position;
+                self.velocity = velocity;
+            }
+            PropagationKind::Numeric => {
+                let acceleration = gravitational_acceleration(
+                    self.position,
+                    parent_gm,
+                    parent.j2,
+                    parent.equatorial_radius,
+                    parent.quaternion,
+                );
+                self.velocity += acceleration * dt;
+                self.position += self.velocity * dt;
+            }
+        }
+    }
+
+    /// Update orbital elements from position and velocity.
+    /// The whole discussion is found in chapter 4.4 in
+    /// https://www.academia.edu/8612052/ORBITAL_MECHANICS_FOR_ENGINEERING_STUDENTS
+    pub(crate) fn update(&mut self, mut others: impl DynIterMut<Item = CelestialBody>) {
+        let parent = match self.parent {
+            Some(parent_id) => others.dyn_iter_mut().find(|body| body.id == parent_id),
+            None => None,
+        };
+        if let Some(parent) = parent {
+            let parent_gm = parent.GM;
+            // Angular momentum vectors
+            let ang = self.velocity.cross(self.position);
+            let r = self.position.magnitude();
+            let v = self.velocity.magnitude();
+            // Node vector
+            let n = Vector3::new(0., 0., 1.).cross(ang);
+            // Eccentricity vector
+            let e = self.position.clone() * (1. / parent_gm * (v * v - parent_gm / r))
+                - self.velocity * (self.position.dot(self.velocity) / parent_gm);
+            self.orbital_elements.eccentricity = e.magnitude();
+            self.orbital_elements.orbit_type = classify_orbit(self.orbital_elements.eccentricity);
+            self.orbital_elements.inclination = (-ang.z / ang.magnitude()).acos();
+            // Avoid zero division
+            if n.magnitude2() <= EPSILON {
+                self.orbital_elements.ascending_node = 0.;
+            } else {
+                self.orbital_elements.ascending_node = (n.x / n.magnitude()).acos();
+                if n.y < 0. {
+                    self.orbital_elements.ascending_node =
+                        2. * std::f64::consts::PI - self.orbital_elements.ascending_node;
+                }
+            }
+            // Vis-viva already goes negative once the body is on an escape
+            // trajectory (e > 1), which is exactly what the hyperbolic state
+            // reconstruction in `state_from_elements` expects.
+            self.orbital_elements.semimajor_axis = 1. / (2. / r - v * v / parent_gm);
+
+            // Rotation to perifocal frame
+            let ascending_node_rot = <Quaternion as Rotation3>::from_axis_angle(
+                Vector3::new(0., 0., 1.),
+                Rad(self.orbital_elements.ascending_node - std::f64::consts::PI / 2.),
+            );
+            let inclination_rot = Quaternion::from_axis_angle(
+                Vector3::new(0., 1., 0.),
+                Rad(std::f64::consts::PI - self.orbital_elements.inclination),
+            );
+            let _plane_rot = ascending_node_rot * inclination_rot;
+
+            // Avoid zero division and still get the correct answer when N == 0.
+            // This is necessary to draw orbit with zero inclination and nonzero eccentricity.
+            if n.magnitude2() <= EPSILON || e.magnitude2() <= EPSILON {
+                self.orbital_elements.argument_of_perihelion =
+                    (if ang.z < 0. { -e.y } else { e.y }).atan2(e.x);
+            } else {
+                self.orbital_elements.argument_of_perihelion =
+                    (n.dot(e) / n.magnitude() / e.magnitude()).acos();
+                if e.z < 0. {
+                    self.orbital_elements.argument_of_perihelion =
+                        2. * std::f64::consts::PI - self.orbital_elements.argument_of_perihelion;
+                }
+            }
+
+            // Recover the true anomaly (and from it, the mean anomaly) from
+            // the state vectors, so a body whose position/velocity were just
+            // set from outside -- e.g. a sphere-of-influence transition --
+            // keeps propagating analytically from the right phase instead of
+            // jumping back to wherever it was on its previous orbit.
+            if e.magnitude2() > EPSILON {
+                let mut true_anomaly = (e.dot(self.position) / (e.magnitude() * r))
+                    .clamp(-1., 1.)
+                    .acos();
+                if self.position.dot(self.velocity) < 0. {
+                    true_anomaly = 2. * std::f64::consts::PI - true_anomaly;
+                }
+                self.orbital_elements.mean_anomaly = mean_anomaly_from_true_anomaly(
+                    true_anomaly,
+                    self.orbital_elements.eccentricity,
+                    self.orbital_elements.orbit_type,
+                );
+            }
+        }
+    }
+}
+
+/// Newtonian gravitational constant, in km^3 kg^-1 s^-2, for converting a
+/// `CelestialBodyBuilder`'s mass in kg into this crate's normalized `GM`.
+pub(crate) const GRAVITATIONAL_CONSTANT: f64 = 6.674e-20;
+
+/// A body's orbital elements in the human units mission-design references
+/// and catalogs actually publish them in -- km and degrees -- rather than
+/// this crate's internal AU/radian representation. Consumed by
+/// `CelestialBodyBuilder::elements`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BuilderElements {
+    pub semimajor_axis_km: f64,
+    pub eccentricity: f64,
+    pub inclination_deg: f64,
+    pub ascending_node_deg: f64,
+    pub argument_of_perihelion_deg: f64,
+    pub mean_anomaly_deg: f64,
+    pub epoch: f64,
+    pub soi_km: f64,
+}
+
+/// Chained, human-unit alternative to `CelestialBody::from_orbital_elements`:
+/// mass in kg, radius/semimajor axis/SOI in km, angles in degrees, all
+/// converted to this crate's units in one place (`build`): GM and
+/// semimajor axis/SOI normalized to AU, radius kept in km as `radius` is stored,
+/// instead of scattering `/ AU` and `* rad_per_deg` conversions across every
+/// call site. Mirrors systemic's `OrbitalBuilder`.
+pub struct CelestialBodyBuilder {
+    name: String,
+    orbit_color: String,
+    parent: Option<usize>,
+    elements: BuilderElements,
+    mass_kg: f64,
+    radius_km: f64,
+    params: AddPlanetParams,
+    j2: Option<(f64, f64)>,
+    propagation: PropagationKind,
+}
+
+impl CelestialBodyBuilder {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            orbit_color: "#ffffff".to_string(),
+            parent: None,
+            elements: BuilderElements::default(),
+            mass_kg: 0.,
+            radius_km: 0.,
+            params: AddPlanetParams::default(),
+            j2: None,
+            propagation: PropagationKind::Analytic,
+        }
+    }
+
+    /// Orbit `parent_id` rather than being a root body (e.g. the sun).
+    pub fn orbiting(mut self, parent_id: usize) -> Self {
+        self.parent = Some(parent_id);
+        self
+    }
+
+    /// Orbital elements in km/degrees, as published by mission-design
+    /// references (as opposed to this crate's internal AU/radian fields).
+    pub fn elements(mut self, elements: BuilderElements) -> Self {
+        self.elements = elements;
+        self
+    }
+
+    /// Mass in kg and radius in km. The mass is converted to `GM` via
+    /// `GRAVITATIONAL_CONSTANT`; the radius is stored in km unchanged.
+    pub fn physical(mut self, mass_kg: f64, radius_km: f64) -> Self {
+        self.mass_kg = mass_kg;
+        self.radius_km = radius_km;
+        self
+    }
+
+    /// Axial tilt, rotation period and initial quaternion/angular velocity.
+    pub fn params(mut self, params: AddPlanetParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Zonal-harmonic oblateness felt by this body's children, see
+    /// `CelestialBody::with_j2`. `equatorial_radius_km` is converted to AU.
+    pub fn j2(mut self, j2: f64, equatorial_radius_km: f64) -> Self {
+        self.j2 = Some((j2, equatorial_radius_km));
+        self
+    }
+
+    /// Force-integrate this body (e.g. a rocket that may thrust) instead of
+    /// propagating it analytically off its mean anomaly.
+    pub fn propagation(mut self, propagation: PropagationKind) -> Self {
+        self.propagation = propagation;
+        self
+    }
+
+    pub fn orbit_color(mut self, orbit_color: impl Into<String>) -> Self {
+        self.orbit_color = orbit_color.into();
+        self
+    }
+
+    /// Convert the accumulated human-unit fields into this crate's
+    /// normalized `OrbitalElements`/`GM`, construct the body, register it
+    /// with `universe`, and return the new id.
+    #[allow(non_snake_case)]
+    pub fn build(self, universe: &mut Universe) -> usize {
+        let rad_per_deg = std::f64::consts::PI / 180.;
+        let GM = GRAVITATIONAL_CONSTANT * self.mass_kg / AU / AU / AU;
+        let orbital_elements = OrbitalElements {
+            semimajor_axis: self.elements.semimajor_axis_km / AU,
+            eccentricity: self.elements.eccentricity,
+            inclination: self.elements.inclination_deg * rad_per_deg,
+            ascending_node: self.elements.ascending_node_deg * rad_per_deg,
+            argument_of_perihelion: self.elements.argument_of_perihelion_deg * rad_per_deg,
+            epoch: self.elements.epoch,
+            mean_anomaly: self.elements.mean_anomaly_deg * rad_per_deg,
+            soi: self.elements.soi_km / AU,
+            orbit_type: classify_orbit(self.elements.eccentricity),
+        };
+
+        let mut body = match self.parent {
+            Some(parent_id) => CelestialBody::from_orbital_elements(
+                universe,
+                Some(parent_id),
+                orbital_elements,
+                self.params,
+                GM,
+                self.radius_km,
+                self.name,
+            ),
+            None => CelestialBody::new(
+                universe,
+                None,
+                Vector3::zero(),
+                self.orbit_color.clone(),
+                GM,
+                self.name,
+                orbital_elements,
+            ),
+        };
+
+        if let Some((j2, equatorial_radius_km)) = self.j2 {
+            body = body.with_j2(j2, equatorial_radius_km / AU);
+        }
+        body.propagation = self.propagation;
+        if self.parent.is_some() {
+            body.orbit_color = self.orbit_color;
+        }
+
+        let id = body.id;
+        universe.add_body(body);
+        id
+    }
+}
+
+struct ChildrenList<'a>(&'a [usize]);
+
+impl Serialize for ChildrenList<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut children = serializer.serialize_seq(Some(self.0.len()))?;
+        for child in self.0.iter() {
+            children.serialize_element(child)?;
+        }
+        children.end()
+    }
+}
+
+impl Serialize for CelestialBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(4))?;
+        map.serialize_entry("id", &self.id)?;
+        map.serialize_entry("name", &self.name)?;
+        map.serialize_entry("position", &self.position)?;
+        map.serialize_entry("velocity", &self.velocity)?;
+        map.serialize_entry("quaternion", &self.quaternion)?;
+        map.serialize_entry("angular_velocity", &self.angular_velocity)?;
+        map.serialize_entry("orbit_color", &self.orbit_color)?;
+        map.serialize_entry("children", &ChildrenList(&self.children))?;
+        map.serialize_entry("parent", &self.parent.unwrap_or(0))?;
+        map.serialize_entry("radius", &self.radius)?;
+        map.serialize_entry("GM", &self.GM)?;
+        map.serialize_entry("orbital_elements", &self.orbital_elements)?;
+        map.end()
+    }
+}
+
+#[test]
+fn serialize_cel() {
+    let cel = CelestialBody {
+        id: 0,
+        name: "sun".to_string(),
+        session_id: None,
+        position: Vector3::new(0., 0., 0.),
+        velocity: Vector3::new(0., 0., 0.),
+        quaternion: Quaternion::new(1., 0., 0., 0.),
+        angular_velocity: Vector3::new(0., 0., 0.),
+        orbit_color: "".to_string(),
+        children: vec![],
+        parent: None,
+        GM: crate::GMsun,
+        radius: crate::RSUN,
+        j2: 0.,
+        equatorial_radius: 0.,
+        propagation: PropagationKind::Analytic,
+        orbital_elements: OrbitalElements::default(),
+    };
+
+    let ser = serde_json::to_value(&cel).unwrap();
+    assert_eq!(
+        ser,
+        serde_json::json!({
+            "id": 0,
+            "name": "sun",
+            "position": {"x": 0., "y": 0., "z": 0.},
+            "velocity": {"x": 0., "y": 0., "z": 0.},
+            "quaternion": {"s": 1., "v": {"x": 0., "y": 0., "z": 0.}},
+            "angular_velocity": {"x": 0., "y": 0., "z": 0.},
+            "orbit_color": "",
+            "children": [],
+            "parent": 0,
+            "radius": crate::RSUN,
+            "GM": crate::GMsun,
+            "orbital_elements": {
+                "semimajor_axis": 0.,
+                "ascending_node": 0.,
+                "inclination": 0.,
+                "eccentricity": 0.,
+                "epoch": 0.,
+                "mean_anomaly": 0.,
+                "argument_of_perihelion": 0.,
+                "soi": 0.,
+                "orbit_type": "Elliptic",
+            },
+        })
+    );
+}
+
+#[test]
+fn j2_acceleration_follows_parent_tilt() {
+    // An off-axis, LEO-scale position: on `from_angle_x`'s rotation axis
+    // itself, or far out at interplanetary scale, the tilt either leaves
+    // `position` invariant or shrinks the J2 term below float noise.
+    let position = Vector3::new(7000., 3000., 2000.) / AU;
+    let parent_gm = 398600. / AU / AU / AU;
+    let parent_j2 = 1.08263e-3;
+    let parent_equatorial_radius = 6378. / AU;
+
+    let untilted = gravitational_acceleration(
+        position,
+        parent_gm,
+        parent_j2,
+        parent_equatorial_radius,
+        Quaternion::new(1., 0., 0., 0.),
+    );
+    let tilted = gravitational_acceleration(
+        position,
+        parent_gm,
+        parent_j2,
+        parent_equatorial_radius,
+        <Quaternion as Rotation3>::from_angle_x(Rad(23.4392811 * std::f64::consts::PI / 180.)),
+    );
+
+    let relative_difference = (untilted - tilted).magnitude() / untilted.magnitude();
+    assert!(
+        relative_difference > 1e-5,
+        "J2 perturbation ignored the parent's axial tilt: {:?} == {:?}",
+        untilted,
+        tilted
+    );
+}
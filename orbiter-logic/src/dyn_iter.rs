@@ -0,0 +1,34 @@
+/// Iterate mutably over a logical collection of items assembled from several
+/// disjoint slices, so a body can look up any other body (in practice: its
+/// parent) while the rest of `Universe::bodies` is borrowed elsewhere.
+pub trait DynIterMut {
+    type Item;
+
+    fn dyn_iter_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Item> + '_>;
+}
+
+/// A single mutable slice, exposed as a `DynIterMut`.
+pub struct MutRef<'a, T>(pub &'a mut [T]);
+
+impl<'a, T> DynIterMut for MutRef<'a, T> {
+    type Item = T;
+
+    fn dyn_iter_mut(&mut self) -> Box<dyn Iterator<Item = &mut T> + '_> {
+        Box::new(self.0.iter_mut())
+    }
+}
+
+/// Two `DynIterMut`s chained end to end.
+pub struct Chained<A, B>(pub A, pub B);
+
+impl<A, B> DynIterMut for Chained<A, B>
+where
+    A: DynIterMut,
+    B: DynIterMut<Item = A::Item>,
+{
+    type Item = A::Item;
+
+    fn dyn_iter_mut(&mut self) -> Box<dyn Iterator<Item = &mut Self::Item> + '_> {
+        Box::new(self.0.dyn_iter_mut().chain(self.1.dyn_iter_mut()))
+    }
+}
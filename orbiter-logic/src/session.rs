@@ -0,0 +1,14 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Identifies the player-controlled rocket spawned by `Universe::new_rocket`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct SessionId(u64);
+
+impl SessionId {
+    pub fn new() -> Self {
+        Self(NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
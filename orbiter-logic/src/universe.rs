@@ -1,13 +1,32 @@
 use crate::{
-    celestial_body::{AddPlanetParams, CelestialBody, OrbitalElements},
+    celestial_body::{
+        gravitational_acceleration, AddPlanetParams, BuilderElements, CelestialBody,
+        CelestialBodyBuilder, OrbitalElements, OrbitType, PropagationKind, TleBodyParams,
+        TleParseError, GRAVITATIONAL_CONSTANT,
+    },
     dyn_iter::{Chained, DynIterMut, MutRef},
     session::SessionId,
     GMsun, Quaternion, Vector3, AU,
 };
-use cgmath::{Rad, Rotation3, Zero};
+use cgmath::{InnerSpace, Rad, Rotation3, Zero};
 use rand::prelude::*;
 use serde::{ser::SerializeMap, Serialize, Serializer};
 
+/// How bodies under `PropagationKind::Numeric` are force-integrated each
+/// substep.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum IntegratorKind {
+    /// Semi-implicit Euler: kick using the acceleration at the start of the
+    /// substep, then drift using the updated velocity. Simple, but only
+    /// first-order accurate.
+    #[default]
+    Explicit,
+    /// Symplectic kick-drift-kick leapfrog: half-kick, drift, recompute
+    /// acceleration, half-kick. Conserves energy much better over many
+    /// orbits, at the cost of one extra acceleration evaluation per substep.
+    Leapfrog,
+}
+
 #[derive(Debug)]
 pub struct Universe {
     pub bodies: Vec<CelestialBody>,
@@ -17,6 +36,7 @@ pub struct Universe {
     start_time: f64,
     time: usize,
     pub time_scale: f64,
+    pub integrator: IntegratorKind,
 }
 
 impl Universe {
@@ -29,139 +49,104 @@ impl Universe {
             start_time: 0.,
             time: 0,
             time_scale: 1.,
+            integrator: IntegratorKind::Explicit,
         };
 
-        let sun = CelestialBody::new(
-            &mut this,
-            None,
-            Vector3::zero(),
-            "#ffffff".to_string(),
-            GMsun,
-            "sun".to_string(),
-            OrbitalElements::default(),
-        );
-        let sun_id = sun.id;
-        this.add_body(sun);
+        let sun_id = CelestialBodyBuilder::new("sun")
+            .physical(GMsun / GRAVITATIONAL_CONSTANT * AU * AU * AU, 0.)
+            .build(&mut this);
 
         let rad_per_deg = std::f64::consts::PI / 180.;
 
-        let params = AddPlanetParams {
+        let earth_params = AddPlanetParams {
             axial_tilt: 23.4392811 * rad_per_deg,
             rotation_period: ((23. * 60. + 56.) * 60. + 4.10),
-            // soi: 5e5,
             quaternion: Quaternion::new(1., 0., 0., 0.),
             angular_velocity: Vector3::zero(),
         };
 
-        let earth = CelestialBody::from_orbital_elements(
-            &mut this,
-            Some(sun_id),
-            OrbitalElements {
-                semimajor_axis: 1.,
+        let earth_id = CelestialBodyBuilder::new("earth")
+            .orbiting(sun_id)
+            .elements(BuilderElements {
+                semimajor_axis_km: 1. * AU,
                 eccentricity: 0.0167086,
-                inclination: 0.,
-                ascending_node: -11.26064 * rad_per_deg,
-                argument_of_perihelion: 114.20783 * rad_per_deg,
-                epoch: 0.,
-                mean_anomaly: 0.,
-                soi: 1.,
-            },
-            params,
-            398600. / AU / AU / AU,
-            6534.,
-            "earth".to_string(),
-        );
-        let earth_id = earth.id;
-
-        this.add_body(earth);
-
-        let mut rocket = CelestialBody::from_orbital_elements(
-            &mut this,
-            Some(earth_id),
-            OrbitalElements {
-                semimajor_axis: 10000. / AU,
-                eccentricity: 0.,
-                inclination: 0.,
-                ascending_node: 0.,
-                argument_of_perihelion: 0.,
+                inclination_deg: 0.,
+                ascending_node_deg: -11.26064,
+                argument_of_perihelion_deg: 114.20783,
+                mean_anomaly_deg: 0.,
                 epoch: 0.,
-                mean_anomaly: 0.,
-                soi: 1.,
-            },
-            AddPlanetParams::default(),
-            100. / AU / AU / AU,
-            0.1,
-            "rocket".to_string(),
-        );
-
-        let rot = <Quaternion as Rotation3>::from_angle_x(Rad(std::f64::consts::PI / 2.))
-            * <Quaternion as Rotation3>::from_angle_y(Rad(std::f64::consts::PI / 2.));
-        rocket.quaternion = rot;
-
-        this.add_body(rocket);
+                soi_km: 1. * AU,
+            })
+            .physical(398600. / GRAVITATIONAL_CONSTANT, 6534.)
+            .params(earth_params)
+            // Earth's oblateness, so satellites in Numeric propagation show
+            // nodal regression / apsidal precession instead of a fixed conic.
+            .j2(1.08263e-3, 6378.137)
+            .build(&mut this);
+
+        let rocket_params = AddPlanetParams {
+            quaternion: <Quaternion as Rotation3>::from_angle_x(Rad(std::f64::consts::PI / 2.))
+                * <Quaternion as Rotation3>::from_angle_y(Rad(std::f64::consts::PI / 2.)),
+            ..AddPlanetParams::default()
+        };
 
-        let moon = CelestialBody::from_orbital_elements(
-            &mut this,
-            Some(earth_id),
-            OrbitalElements {
-                semimajor_axis: 384399. / AU,
+        CelestialBodyBuilder::new("rocket")
+            .orbiting(earth_id)
+            .elements(BuilderElements {
+                semimajor_axis_km: 10000.,
+                soi_km: 1. * AU,
+                ..BuilderElements::default()
+            })
+            .physical(100. / GRAVITATIONAL_CONSTANT, 0.1)
+            .params(rocket_params)
+            // Rockets may thrust, so they can't be assumed to stay on a fixed conic.
+            .propagation(PropagationKind::Numeric)
+            .build(&mut this);
+
+        CelestialBodyBuilder::new("moon")
+            .orbiting(earth_id)
+            .elements(BuilderElements {
+                semimajor_axis_km: 384399.,
                 eccentricity: 0.048775,
-                inclination: -11.26064 * rad_per_deg,
-                ascending_node: 100.492 * rad_per_deg,
-                argument_of_perihelion: 114.20783 * rad_per_deg, //275.066 * rad_per_deg,
+                inclination_deg: -11.26064,
+                ascending_node_deg: 100.492,
+                argument_of_perihelion_deg: 114.20783, //275.066
+                mean_anomaly_deg: 0.,
                 epoch: 0.,
-                mean_anomaly: 0.,
-                soi: 1e5,
-            },
-            AddPlanetParams::default(),
-            4904.8695 / AU / AU / AU,
-            1737.1,
-            "moon".to_string(),
-        );
-
-        this.add_body(moon);
-
-        let mars = CelestialBody::from_orbital_elements(
-            &mut this,
-            Some(sun_id),
-            OrbitalElements {
-                semimajor_axis: 1.523679,
+                soi_km: 1e5 * AU,
+            })
+            .physical(4904.8695 / GRAVITATIONAL_CONSTANT, 1737.1)
+            .build(&mut this);
+
+        CelestialBodyBuilder::new("mars")
+            .orbiting(sun_id)
+            .elements(BuilderElements {
+                semimajor_axis_km: 1.523679 * AU,
                 eccentricity: 0.0935,
-                inclination: 1.850 * rad_per_deg,
-                ascending_node: 49.562 * rad_per_deg,
-                argument_of_perihelion: 286.537 * rad_per_deg,
+                inclination_deg: 1.850,
+                ascending_node_deg: 49.562,
+                argument_of_perihelion_deg: 286.537,
+                mean_anomaly_deg: 0.,
                 epoch: 0.,
-                mean_anomaly: 0.,
-                soi: 3e5,
-            },
-            AddPlanetParams::default(),
-            42828. / AU / AU / AU,
-            3389.5,
-            "mars".to_string(),
-        );
-
-        this.add_body(mars);
-
-        let jupiter = CelestialBody::from_orbital_elements(
-            &mut this,
-            Some(sun_id),
-            OrbitalElements {
-                semimajor_axis: 5.204267,
+                soi_km: 3e5 * AU,
+            })
+            .physical(42828. / GRAVITATIONAL_CONSTANT, 3389.5)
+            .build(&mut this);
+
+        CelestialBodyBuilder::new("jupiter")
+            .orbiting(sun_id)
+            .elements(BuilderElements {
+                semimajor_axis_km: 5.204267 * AU,
                 eccentricity: 0.048775,
-                inclination: 1.305 * rad_per_deg,
-                ascending_node: 100.492 * rad_per_deg,
-                argument_of_perihelion: 275.066 * rad_per_deg,
+                inclination_deg: 1.305,
+                ascending_node_deg: 100.492,
+                argument_of_perihelion_deg: 275.066,
+                mean_anomaly_deg: 0.,
                 epoch: 0.,
-                mean_anomaly: 0.,
-                soi: 10e6,
-            },
-            AddPlanetParams::default(),
-            126686534. / AU / AU / AU,
-            69911.,
-            "jupiter".to_string(),
-        );
-
-        this.add_body(jupiter);
+                soi_km: 10e6 * AU,
+            })
+            .physical(126686534. / GRAVITATIONAL_CONSTANT, 69911.)
+            .build(&mut this);
 
         this
     }
@@ -189,6 +174,7 @@ impl Universe {
                 epoch: 0.,
                 mean_anomaly: 0.,
                 soi: 1.,
+                orbit_type: OrbitType::Elliptic,
             },
             AddPlanetParams {
                 axial_tilt: 0.,
@@ -204,6 +190,7 @@ impl Universe {
         let rot = <Quaternion as Rotation3>::from_angle_x(Rad(std::f64::consts::PI / 2.))
             * <Quaternion as Rotation3>::from_angle_y(Rad(std::f64::consts::PI / 2.));
         rocket.quaternion = rot;
+        rocket.propagation = PropagationKind::Numeric;
 
         let session_id = SessionId::new();
         rocket.session_id = Some(session_id);
@@ -213,7 +200,56 @@ impl Universe {
         session_id
     }
 
-    fn add_body(&mut self, body: CelestialBody) {
+    /// Batch-import satellites from two-line element sets, each given as
+    /// `(name, line1, line2)`, e.g. pasted from a Celestrak catalog export.
+    /// Uses the same placeholder GM/radius as `new_rocket`, since a TLE
+    /// alone carries no mass or size information. Parses every entry before
+    /// adding any of them, so a parse failure partway through the batch
+    /// leaves the universe untouched instead of adding bodies a caller has
+    /// no ids for. Returns the new ids, or the first parse error.
+    pub fn add_satellites_from_tle(
+        &mut self,
+        parent: Option<usize>,
+        satellites: &[(String, String, String)],
+    ) -> Result<Vec<usize>, TleParseError> {
+        let id_gen = self.id_gen;
+        let parsed = satellites
+            .iter()
+            .map(|(name, line1, line2)| {
+                CelestialBody::from_tle(
+                    self,
+                    parent,
+                    line1,
+                    line2,
+                    name.clone(),
+                    TleBodyParams {
+                        GM: 100. / AU / AU / AU,
+                        radius: 0.1,
+                        params: AddPlanetParams::default(),
+                    },
+                )
+            })
+            .collect::<Result<Vec<_>, _>>();
+        let parsed = match parsed {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                // Give back the ids taken by entries parsed before the failure.
+                self.id_gen = id_gen;
+                return Err(err);
+            }
+        };
+
+        Ok(parsed
+            .into_iter()
+            .map(|satellite| {
+                let id = satellite.id;
+                self.add_body(satellite);
+                id
+            })
+            .collect())
+    }
+
+    pub(crate) fn add_body(&mut self, body: CelestialBody) {
         let body_id = body.id;
         if let Some(parent) = body.parent {
             let parent = &mut self.bodies[parent];
@@ -239,10 +275,27 @@ impl Universe {
         let mut bodies = std::mem::take(&mut self.bodies);
 
         let div = 100;
+        let dt = self.time_scale / div as f64;
         for _ in 0..div {
+            // Analytic bodies propagate exactly off their own mean anomaly,
+            // independent of integrator choice, so they always go through
+            // `simulate_body` as before.
             for i in 0..bodies.len() {
-                let (center, chained) = split_bodies(&mut bodies, i);
-                center.simulate_body(chained, self.time_scale, div as f64);
+                if bodies[i].propagation == PropagationKind::Analytic {
+                    let (center, chained) = split_bodies(&mut bodies, i);
+                    center.simulate_body(chained, dt);
+                }
+            }
+            match self.integrator {
+                IntegratorKind::Explicit => {
+                    for i in 0..bodies.len() {
+                        if bodies[i].propagation == PropagationKind::Numeric {
+                            let (center, chained) = split_bodies(&mut bodies, i);
+                            center.simulate_body(chained, dt);
+                        }
+                    }
+                }
+                IntegratorKind::Leapfrog => leapfrog_substep(&mut bodies, dt),
             }
         }
         for i in 0..bodies.len() {
@@ -250,10 +303,90 @@ impl Universe {
             center.update(chained);
         }
         self.bodies = bodies;
+
+        if self.reparent_bodies() {
+            // Elements were computed against the old parent above; redo it
+            // now that `parent`/`position`/`velocity` reflect the new one.
+            let mut bodies = std::mem::take(&mut self.bodies);
+            for i in 0..bodies.len() {
+                let (center, chained) = split_bodies(&mut bodies, i);
+                center.update(chained);
+            }
+            self.bodies = bodies;
+        }
+
         self.time += 1;
         self.sim_time += self.time_scale;
     }
 
+    /// Patched-conic sphere-of-influence transitions: a body that has
+    /// drifted outside its parent's SOI is handed off to the grandparent,
+    /// and a body that has drifted inside a sibling's SOI is handed off to
+    /// that sibling. Position and velocity are converted into the new
+    /// parent's frame so they stay continuous across the handoff. Returns
+    /// whether any body was reparented.
+    fn reparent_bodies(&mut self) -> bool {
+        let mut transitioned = false;
+
+        for i in 0..self.bodies.len() {
+            let Some(parent_id) = self.bodies[i].parent else {
+                continue;
+            };
+
+            let distance_to_parent = self.bodies[i].position.magnitude();
+            if distance_to_parent > self.bodies[parent_id].soi() {
+                // The root (e.g. the Sun) has no grandparent to escape to, so
+                // bodies parented to it fall through to the sibling check
+                // below instead of being stuck here forever.
+                if let Some(grandparent_id) = self.bodies[parent_id].parent {
+                    let new_position = self.bodies[i].position + self.bodies[parent_id].position;
+                    let new_velocity = self.bodies[i].velocity + self.bodies[parent_id].velocity;
+                    self.reparent(i, parent_id, grandparent_id, new_position, new_velocity);
+                    transitioned = true;
+                    continue;
+                }
+            }
+
+            let siblings = self.bodies[parent_id].children.clone();
+            for sibling_id in siblings {
+                if sibling_id == i {
+                    continue;
+                }
+                let sibling_soi = self.bodies[sibling_id].soi();
+                if sibling_soi <= 0. {
+                    continue;
+                }
+                let position_from_sibling = self.bodies[i].position - self.bodies[sibling_id].position;
+                if position_from_sibling.magnitude() < sibling_soi {
+                    let new_velocity = self.bodies[i].velocity - self.bodies[sibling_id].velocity;
+                    self.reparent(i, parent_id, sibling_id, position_from_sibling, new_velocity);
+                    transitioned = true;
+                    break;
+                }
+            }
+        }
+
+        transitioned
+    }
+
+    fn reparent(
+        &mut self,
+        body_id: usize,
+        old_parent_id: usize,
+        new_parent_id: usize,
+        new_position: Vector3,
+        new_velocity: Vector3,
+    ) {
+        self.bodies[old_parent_id]
+            .children
+            .retain(|&child_id| child_id != body_id);
+
+        self.bodies[body_id].parent = Some(new_parent_id);
+        self.bodies[body_id].position = new_position;
+        self.bodies[body_id].velocity = new_velocity;
+        self.bodies[new_parent_id].children.push(body_id);
+    }
+
     pub fn get_time(&self) -> usize {
         self.time
     }
@@ -263,6 +396,56 @@ impl Universe {
     }
 }
 
+/// Gravitational acceleration felt by `body` due to its parent (in practice:
+/// the only force this crate's hierarchical model accounts for), looked up
+/// by indexing `bodies` directly since a body's `parent` is its index into
+/// that same slice.
+fn acceleration_on(body: &CelestialBody, bodies: &[CelestialBody]) -> Vector3 {
+    match body.parent {
+        Some(parent_id) => {
+            let parent = &bodies[parent_id];
+            gravitational_acceleration(
+                body.position,
+                parent.GM,
+                parent.j2,
+                parent.equatorial_radius,
+                parent.quaternion,
+            )
+        }
+        None => Vector3::zero(),
+    }
+}
+
+/// Symplectic kick-drift-kick leapfrog substep for bodies under
+/// `PropagationKind::Numeric`: half-kick velocities using the acceleration
+/// field at the start of the substep, drift positions, then half-kick again
+/// using the field recomputed at the drifted positions. Unlike
+/// `simulate_body`'s per-body stepping (which integrates each body against
+/// whatever state its parent already has for this substep), this computes
+/// every body's acceleration from the same snapshot of positions first, so
+/// the two kicks bracket a single consistent drift.
+fn leapfrog_substep(bodies: &mut [CelestialBody], dt: f64) {
+    let accelerations: Vec<Vector3> = bodies.iter().map(|body| acceleration_on(body, bodies)).collect();
+    for (body, acceleration) in bodies.iter_mut().zip(&accelerations) {
+        if body.propagation == PropagationKind::Numeric {
+            body.velocity += *acceleration * (dt / 2.);
+        }
+    }
+
+    for body in bodies.iter_mut() {
+        if body.propagation == PropagationKind::Numeric {
+            body.position += body.velocity * dt;
+        }
+    }
+
+    let accelerations: Vec<Vector3> = bodies.iter().map(|body| acceleration_on(body, bodies)).collect();
+    for (body, acceleration) in bodies.iter_mut().zip(&accelerations) {
+        if body.propagation == PropagationKind::Numeric {
+            body.velocity += *acceleration * (dt / 2.);
+        }
+    }
+}
+
 impl Serialize for Universe {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -285,3 +468,340 @@ pub fn serialize(this: &Universe) -> serde_json::Result<String> {
 fn test_universe() {
     let _ = Universe::new();
 }
+
+#[test]
+fn earth_escape_transitions_to_sun() {
+    let mut universe = Universe {
+        bodies: vec![],
+        root: 0,
+        id_gen: 0,
+        sim_time: 0.,
+        start_time: 0.,
+        time: 0,
+        time_scale: 0.,
+        integrator: IntegratorKind::Explicit,
+    };
+
+    let sun = CelestialBody::new(
+        &mut universe,
+        None,
+        Vector3::zero(),
+        "#ffffff".to_string(),
+        GMsun,
+        "sun".to_string(),
+        OrbitalElements::default(),
+    );
+    let sun_id = sun.id;
+    universe.add_body(sun);
+
+    let earth = CelestialBody::from_orbital_elements(
+        &mut universe,
+        Some(sun_id),
+        OrbitalElements {
+            semimajor_axis: 1.,
+            eccentricity: 0.,
+            inclination: 0.,
+            ascending_node: 0.,
+            argument_of_perihelion: 0.,
+            epoch: 0.,
+            mean_anomaly: 0.,
+            soi: 1000. / AU,
+            orbit_type: OrbitType::Elliptic,
+        },
+        AddPlanetParams::default(),
+        398600. / AU / AU / AU,
+        6378.,
+        "earth".to_string(),
+    );
+    let earth_id = earth.id;
+    universe.add_body(earth);
+
+    // Parked just outside Earth's (shrunk, for this test) SOI.
+    let rocket = CelestialBody::from_orbital_elements(
+        &mut universe,
+        Some(earth_id),
+        OrbitalElements {
+            semimajor_axis: 1100. / AU,
+            eccentricity: 0.,
+            inclination: 0.,
+            ascending_node: 0.,
+            argument_of_perihelion: 0.,
+            epoch: 0.,
+            mean_anomaly: 0.,
+            soi: 1.,
+            orbit_type: OrbitType::Elliptic,
+        },
+        AddPlanetParams::default(),
+        100. / AU / AU / AU,
+        0.1,
+        "rocket".to_string(),
+    );
+    let rocket_id = rocket.id;
+    universe.add_body(rocket);
+
+    universe.update();
+
+    let rocket = universe.bodies.iter().find(|b| b.id == rocket_id).unwrap();
+    assert_eq!(rocket.parent, Some(sun_id));
+}
+
+#[test]
+fn moon_capture_transitions_from_earth() {
+    let mut universe = Universe {
+        bodies: vec![],
+        root: 0,
+        id_gen: 0,
+        sim_time: 0.,
+        start_time: 0.,
+        time: 0,
+        time_scale: 0.,
+        integrator: IntegratorKind::Explicit,
+    };
+
+    let sun = CelestialBody::new(
+        &mut universe,
+        None,
+        Vector3::zero(),
+        "#ffffff".to_string(),
+        GMsun,
+        "sun".to_string(),
+        OrbitalElements::default(),
+    );
+    let sun_id = sun.id;
+    universe.add_body(sun);
+
+    let earth = CelestialBody::from_orbital_elements(
+        &mut universe,
+        Some(sun_id),
+        OrbitalElements {
+            semimajor_axis: 1.,
+            eccentricity: 0.,
+            inclination: 0.,
+            ascending_node: 0.,
+            argument_of_perihelion: 0.,
+            epoch: 0.,
+            mean_anomaly: 0.,
+            soi: 1e6 / AU,
+            orbit_type: OrbitType::Elliptic,
+        },
+        AddPlanetParams::default(),
+        398600. / AU / AU / AU,
+        6378.,
+        "earth".to_string(),
+    );
+    let earth_id = earth.id;
+    universe.add_body(earth);
+
+    let moon = CelestialBody::from_orbital_elements(
+        &mut universe,
+        Some(earth_id),
+        OrbitalElements {
+            semimajor_axis: 1000. / AU,
+            eccentricity: 0.,
+            inclination: 0.,
+            ascending_node: 0.,
+            argument_of_perihelion: 0.,
+            epoch: 0.,
+            mean_anomaly: 0.,
+            soi: 50. / AU,
+            orbit_type: OrbitType::Elliptic,
+        },
+        AddPlanetParams::default(),
+        4904.8695 / AU / AU / AU,
+        1737.1,
+        "moon".to_string(),
+    );
+    let moon_id = moon.id;
+    universe.add_body(moon);
+
+    // A sibling of the moon, parked well inside the moon's SOI.
+    let rocket = CelestialBody::from_orbital_elements(
+        &mut universe,
+        Some(earth_id),
+        OrbitalElements {
+            semimajor_axis: 980. / AU,
+            eccentricity: 0.,
+            inclination: 0.,
+            ascending_node: 0.,
+            argument_of_perihelion: 0.,
+            epoch: 0.,
+            mean_anomaly: 0.,
+            soi: 0.,
+            orbit_type: OrbitType::Elliptic,
+        },
+        AddPlanetParams::default(),
+        100. / AU / AU / AU,
+        0.1,
+        "rocket".to_string(),
+    );
+    let rocket_id = rocket.id;
+    universe.add_body(rocket);
+
+    universe.update();
+
+    let rocket = universe.bodies.iter().find(|b| b.id == rocket_id).unwrap();
+    assert_eq!(rocket.parent, Some(moon_id));
+}
+
+#[test]
+fn sibling_capture_transitions_from_root_parent() {
+    // The root (here: the Sun) has no grandparent to escape to and its
+    // `soi()` is 0 with default `OrbitalElements`, so every body parented
+    // directly to it is permanently "outside" its SOI. That must still
+    // fall through to the sibling check instead of getting stuck.
+    let mut universe = Universe {
+        bodies: vec![],
+        root: 0,
+        id_gen: 0,
+        sim_time: 0.,
+        start_time: 0.,
+        time: 0,
+        time_scale: 0.,
+        integrator: IntegratorKind::Explicit,
+    };
+
+    let sun = CelestialBody::new(
+        &mut universe,
+        None,
+        Vector3::zero(),
+        "#ffffff".to_string(),
+        GMsun,
+        "sun".to_string(),
+        OrbitalElements::default(),
+    );
+    let sun_id = sun.id;
+    universe.add_body(sun);
+
+    let mars = CelestialBody::from_orbital_elements(
+        &mut universe,
+        Some(sun_id),
+        OrbitalElements {
+            semimajor_axis: 1000. / AU,
+            eccentricity: 0.,
+            inclination: 0.,
+            ascending_node: 0.,
+            argument_of_perihelion: 0.,
+            epoch: 0.,
+            mean_anomaly: 0.,
+            soi: 2000. / AU,
+            orbit_type: OrbitType::Elliptic,
+        },
+        AddPlanetParams::default(),
+        42828. / AU / AU / AU,
+        3389.5,
+        "mars".to_string(),
+    );
+    let mars_id = mars.id;
+    universe.add_body(mars);
+
+    // A sibling of Mars, parented directly to the Sun and parked well
+    // inside Mars's SOI.
+    let rocket = CelestialBody::from_orbital_elements(
+        &mut universe,
+        Some(sun_id),
+        OrbitalElements {
+            semimajor_axis: 1005. / AU,
+            eccentricity: 0.,
+            inclination: 0.,
+            ascending_node: 0.,
+            argument_of_perihelion: 0.,
+            epoch: 0.,
+            mean_anomaly: 0.,
+            soi: 0.,
+            orbit_type: OrbitType::Elliptic,
+        },
+        AddPlanetParams::default(),
+        100. / AU / AU / AU,
+        0.1,
+        "rocket".to_string(),
+    );
+    let rocket_id = rocket.id;
+    universe.add_body(rocket);
+
+    universe.update();
+
+    let rocket = universe.bodies.iter().find(|b| b.id == rocket_id).unwrap();
+    assert_eq!(rocket.parent, Some(mars_id));
+}
+
+#[test]
+fn leapfrog_conserves_earth_orbit() {
+    let mut universe = Universe {
+        bodies: vec![],
+        root: 0,
+        id_gen: 0,
+        sim_time: 0.,
+        start_time: 0.,
+        time: 0,
+        time_scale: 0.,
+        integrator: IntegratorKind::Leapfrog,
+    };
+
+    let sun = CelestialBody::new(
+        &mut universe,
+        None,
+        Vector3::zero(),
+        "#ffffff".to_string(),
+        GMsun,
+        "sun".to_string(),
+        OrbitalElements::default(),
+    );
+    let sun_id = sun.id;
+    universe.add_body(sun);
+
+    let mut earth = CelestialBody::from_orbital_elements(
+        &mut universe,
+        Some(sun_id),
+        OrbitalElements {
+            semimajor_axis: 1.,
+            eccentricity: 0.,
+            inclination: 0.,
+            ascending_node: 0.,
+            argument_of_perihelion: 0.,
+            epoch: 0.,
+            mean_anomaly: 0.,
+            soi: 1e5 / AU,
+            orbit_type: OrbitType::Elliptic,
+        },
+        AddPlanetParams::default(),
+        398600. / AU / AU / AU,
+        6378.,
+        "earth".to_string(),
+    );
+    // Force the Numeric (force-integrated) path, since Analytic bodies
+    // propagate off their mean anomaly regardless of integrator choice.
+    earth.propagation = PropagationKind::Numeric;
+    let earth_id = earth.id;
+    universe.add_body(earth);
+
+    let period = 2. * std::f64::consts::PI / (GMsun / 1f64.powi(3)).sqrt();
+    universe.time_scale = period / 100.;
+
+    let initial_r = universe.bodies[earth_id].position.magnitude();
+    let initial_v = universe.bodies[earth_id].velocity.magnitude();
+    let initial_energy = initial_v * initial_v / 2. - GMsun / initial_r;
+    let initial_semimajor_axis = 1. / (2. / initial_r - initial_v * initial_v / GMsun);
+
+    for _ in 0..300 {
+        universe.update();
+    }
+
+    let earth = &universe.bodies[earth_id];
+    let r = earth.position.magnitude();
+    let v = earth.velocity.magnitude();
+    let energy = v * v / 2. - GMsun / r;
+    let semimajor_axis = 1. / (2. / r - v * v / GMsun);
+
+    assert!(
+        (energy - initial_energy).abs() / initial_energy.abs() < 1e-3,
+        "energy drifted: {} -> {}",
+        initial_energy,
+        energy
+    );
+    assert!(
+        (semimajor_axis - initial_semimajor_axis).abs() < 1e-3,
+        "semimajor axis drifted: {} -> {}",
+        initial_semimajor_axis,
+        semimajor_axis
+    );
+}